@@ -0,0 +1,136 @@
+//! Pluggable caching backends for the series catalog.
+//!
+//! `search_by_name` and `search_by_id` both fetch the whole catalog through
+//! [`ReqElencoSerie`](crate::ReqElencoSerie); a cache lets repeated lookups in a session reuse a
+//! previously fetched catalog instead of re-downloading and re-parsing it every time. Two backends
+//! are provided: [`MemoryCache`] keeps the entries in memory for the lifetime of the process,
+//! [`FileCache`] persists them as json on disk so they survive across runs.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Serie;
+
+/// Stored catalog entries, keyed by request url, paired with the time each was stored.
+type Entries = Arc<RwLock<HashMap<String, (u64, Vec<Serie>)>>>;
+
+/// A caching backend keyed by the request url.
+///
+/// Implementations store the fetched catalog together with the moment it was stored so a hit can
+/// be discarded once it is older than the client's configured time-to-live.
+pub trait Cache: Send + Sync {
+    /// Return the cached catalog for `key` if it is present and not older than `ttl`.
+    fn get(&self, key: &str, ttl: Duration) -> Option<Vec<Serie>>;
+
+    /// Store `value` for `key`, stamping it with the current time.
+    fn put(&self, key: &str, value: &[Serie]);
+}
+
+/// Number of seconds elapsed since the unix epoch, used as the storage timestamp.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether an entry stored at `stored_at` is still fresh with respect to `ttl`.
+fn is_fresh(stored_at: u64, ttl: Duration) -> bool {
+    now().saturating_sub(stored_at) <= ttl.as_secs()
+}
+
+/// An in-memory cache backed by a `HashMap` behind an `Arc<RwLock<..>>` so it can be shared and
+/// read concurrently.
+#[derive(Clone, Default)]
+pub struct MemoryCache {
+    entries: Entries,
+}
+
+impl MemoryCache {
+    /// Create an empty in-memory cache.
+    pub fn new() -> MemoryCache {
+        MemoryCache::default()
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str, ttl: Duration) -> Option<Vec<Serie>> {
+        let entries = self.entries.read().ok()?;
+        let (stored_at, value) = entries.get(key)?;
+
+        if is_fresh(*stored_at, ttl) {
+            Some(value.clone())
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: &str, value: &[Serie]) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.insert(key.to_owned(), (now(), value.to_vec()));
+        }
+    }
+}
+
+/// What [`FileCache`] serializes to disk: the catalog together with the time it was stored.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    stored_at: u64,
+    data: Vec<Serie>,
+}
+
+/// An on-disk cache that serializes each catalog as json inside a cache directory.
+///
+/// A hit is read back and discarded when it is older than the time-to-live; any io or parse error
+/// is treated as a miss, so a corrupt or unreadable entry simply triggers a fresh fetch.
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Create a cache storing its entries inside `dir`; the directory is created on the first
+    /// store if it does not exist.
+    pub fn new<P: AsRef<Path>>(dir: P) -> FileCache {
+        FileCache {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the file backing `key`; the key is hashed so it is a valid file name.
+    fn path(&self, key: &str) -> PathBuf {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.json", hasher.finish()))
+    }
+}
+
+impl Cache for FileCache {
+    fn get(&self, key: &str, ttl: Duration) -> Option<Vec<Serie>> {
+        let raw = std::fs::read(self.path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&raw).ok()?;
+
+        if is_fresh(entry.stored_at, ttl) {
+            Some(entry.data)
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: &str, value: &[Serie]) {
+        let entry = CacheEntry {
+            stored_at: now(),
+            data: value.to_vec(),
+        };
+
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+
+        if let Ok(raw) = serde_json::to_vec(&entry) {
+            let _ = std::fs::write(self.path(key), raw);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 //! This crate is a simple library for [subspedia](https://www.subspedia.tv/) based on api the
 //! provided by site
 
-extern crate hyper;
-extern crate hyper_tls;
-extern crate tokio;
-extern crate futures;
+// The `failure` derive macros emit their trait impls inside an anonymous const, which recent
+// compilers flag as non-local; the crate intentionally keeps `failure`, so the lint is silenced.
+#![allow(non_local_definitions)]
+
+extern crate reqwest;
 #[macro_use]
 extern crate serde_derive;
 extern crate failure;
@@ -12,26 +13,84 @@ extern crate failure;
 extern crate failure_derive;
 extern crate serde;
 extern crate serde_json;
+extern crate tokio;
 
-use futures::{Future, Stream};
-use std::sync::{Arc, Mutex};
 use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+mod cache;
+pub use cache::{Cache, FileCache, MemoryCache};
+
+#[cfg(feature = "rss")]
+extern crate quick_xml;
+
+#[cfg(feature = "rss")]
+mod rss;
+#[cfg(feature = "rss")]
+pub use rss::to_rss;
+
+/// Default timeout applied to every request when none is configured on the builder.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of attempts (the first try plus up to four retries) for a transient failure.
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Default base delay for the exponential backoff between retries.
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Upper bound for a single backoff delay, so the exponential growth can't run away.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Default time-to-live for cached catalog entries when none is configured on the builder.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 
 /// An enumeration of possible error which can occur during http requests and the parsing of json
 /// returned by the api
 #[derive(Debug, Fail)]
 pub enum FetchError {
     #[fail(display = "HTTP error: {}", _0)]
-    Http(hyper::Error),
+    Http(reqwest::Error),
     #[fail(display = "JSON parsing error: {}", _0)]
     Json(serde_json::Error),
+    #[fail(display = "request timed out: {}", _0)]
+    Timeout(reqwest::Error),
+    #[fail(display = "IO error: {}", _0)]
+    Io(std::io::Error),
     #[fail(display = "{}", _0)]
     NotFound(String),
+    #[cfg(feature = "rss")]
+    #[fail(display = "RSS generation error: {}", _0)]
+    Rss(String),
 }
 
-impl From<hyper::Error> for FetchError {
-    fn from(err: hyper::Error) -> FetchError {
-        FetchError::Http(err)
+impl FetchError {
+    /// Whether the error is transient and the request is worth retrying.
+    ///
+    /// A timeout, a connection-level failure or a 5xx response are considered transient; a json
+    /// parse error or a [`FetchError::NotFound`] are deterministic and are never retried.
+    fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Timeout(_) => true,
+            FetchError::Http(err) => {
+                err.is_connect()
+                    || err.status().is_some_and(|status| status.is_server_error())
+            }
+            FetchError::Json(_) | FetchError::Io(_) | FetchError::NotFound(_) => false,
+            #[cfg(feature = "rss")]
+            FetchError::Rss(_) => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(err: reqwest::Error) -> FetchError {
+        if err.is_timeout() {
+            FetchError::Timeout(err)
+        } else {
+            FetchError::Http(err)
+        }
     }
 }
 
@@ -41,6 +100,12 @@ impl From<serde_json::Error> for FetchError {
     }
 }
 
+impl From<std::io::Error> for FetchError {
+    fn from(err: std::io::Error) -> FetchError {
+        FetchError::Io(err)
+    }
+}
+
 /// Trait that requests have to implement.
 pub trait Request {
     type Response: serde::de::DeserializeOwned + std::fmt::Debug + std::marker::Send;
@@ -51,6 +116,7 @@ pub trait Request {
 
 /// Struct for store the television series in translation.
 #[derive(Deserialize, Debug)]
+#[allow(dead_code)]
 pub struct SerieTraduzione {
     id_serie: usize,
     nome_serie: String,
@@ -73,7 +139,7 @@ impl Request for ReqSerieTraduzione {
 }
 
 /// Struct for store the television series available on site.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Serie {
     id_serie: usize,
     pub nome_serie: String,
@@ -96,6 +162,7 @@ impl Request for ReqElencoSerie {
 
 /// Struct for store the subtitles.
 #[derive(Deserialize, Debug)]
+#[allow(dead_code)]
 pub struct Sottotitolo {
     id_serie: usize,
     nome_serie: String,
@@ -143,8 +210,306 @@ impl Request for ReqSottotitoliSerie {
     }
 }
 
+/// Client for the subspedia api.
+///
+/// A `Subspedia` owns a single [`reqwest::Client`] built once and reused for every request, so the
+/// connection pool and TLS state are shared across calls instead of being rebuilt each time.
+pub struct Subspedia {
+    client: reqwest::Client,
+    max_attempts: u32,
+    base_delay: Duration,
+    cache: Option<Arc<dyn Cache>>,
+    cache_ttl: Duration,
+}
+
+impl Subspedia {
+    /// Create a new client with the default configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the underlying client (including its TLS backend) fails to initialize.
+    pub fn new() -> Result<Subspedia, FetchError> {
+        Subspedia::builder().build()
+    }
+
+    /// Start building a client, letting you tweak the configuration before creating it.
+    pub fn builder() -> SubspediaBuilder {
+        SubspediaBuilder::new()
+    }
+
+    /// Makes a request based on the given type, returning the parsed response.
+    ///
+    /// Transient failures (timeout, connection error or a 5xx response) are retried up to the
+    /// configured number of attempts using exponential backoff with jitter; deterministic errors
+    /// are surfaced immediately. Only the final error is returned once the attempts are exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if something gone wrong during the http request or while parsing the json.
+    pub async fn get<R: Request>(&self, req: &R) -> Result<Vec<R::Response>, FetchError> {
+        let url = req.url();
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            match self.try_get::<R>(url.as_ref()).await {
+                Ok(result) => return Ok(result),
+                Err(err) => {
+                    if attempt >= self.max_attempts || !err.is_transient() {
+                        return Err(err);
+                    }
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                }
+            }
+        }
+    }
+
+    /// Perform a single request attempt without any retry logic.
+    async fn try_get<R: Request>(&self, url: &str) -> Result<Vec<R::Response>, FetchError> {
+        let body = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        let result = serde_json::from_slice(&body)?;
+        Ok(result)
+    }
+
+    /// Compute the backoff delay before the next attempt: the base delay after the first attempt,
+    /// doubled for every further attempt already made, capped at [`MAX_BACKOFF`] and with some jitter added to avoid a thundering
+    /// herd of synchronised retries.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let exponential = self
+            .base_delay
+            .checked_mul(1u32 << exponent)
+            .unwrap_or(MAX_BACKOFF)
+            .min(MAX_BACKOFF);
+
+        exponential + jitter(exponential)
+    }
+
+    /// Fetch the series catalog, going through the configured cache.
+    ///
+    /// On a cache hit within the configured ttl the http call is skipped entirely; on a miss or an
+    /// expired entry the catalog is fetched and the cache is repopulated.
+    async fn elenco_serie(&self) -> Result<Vec<Serie>, FetchError> {
+        let key = ReqElencoSerie.url();
+
+        if let Some(cache) = &self.cache {
+            if let Some(hit) = cache.get(key.as_ref(), self.cache_ttl) {
+                return Ok(hit);
+            }
+        }
+
+        let series = self.get(&ReqElencoSerie).await?;
+
+        if let Some(cache) = &self.cache {
+            cache.put(key.as_ref(), &series);
+        }
+
+        Ok(series)
+    }
+
+    /// Search the series catalog for the series whose name contains `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if something gone wrong during http requests, parsing json or if a series
+    /// with that name isn't found.
+    pub async fn search_by_name(&self, name: &str) -> Result<Vec<Serie>, FetchError> {
+        let result = self
+            .elenco_serie()
+            .await?
+            .into_iter()
+            .filter(|s| {
+                s.nome_serie
+                    .to_lowercase()
+                    .as_str()
+                    .contains(name.to_lowercase().as_str())
+            })
+            .collect::<Vec<_>>();
+
+        if !result.is_empty() {
+            Ok(result)
+        } else {
+            Err(FetchError::NotFound(format!("Series with name {} not found", name)))
+        }
+    }
+
+    /// Search the series catalog for the series with the given `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if something gone wrong during http requests, parsing json or if a series
+    /// with that id isn't found.
+    pub async fn search_by_id(&self, id: usize) -> Result<Serie, FetchError> {
+        match self
+            .elenco_serie()
+            .await?
+            .into_iter()
+            .find(|s| s.id_serie == id)
+        {
+            Some(s) => Ok(s),
+            None => Err(FetchError::NotFound(format!("Series with id {} not found.", id))),
+        }
+    }
+
+    /// Download the subtitle archive pointed at by `sub.link_file` to `dest`.
+    ///
+    /// The body is streamed to a temporary `.part` file alongside `dest` and atomically renamed on
+    /// success, so an interrupted download never leaves a partial file mistaken for a complete one.
+    /// The request honours the client's timeout and retry settings. Returns the final path.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if something gone wrong during the http request or while writing the file.
+    pub async fn download_subtitle(&self, sub: &Sottotitolo, dest: &Path) -> Result<PathBuf, FetchError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            match self.try_download(&sub.link_file, dest).await {
+                Ok(path) => return Ok(path),
+                Err(err) => {
+                    if attempt >= self.max_attempts || !err.is_transient() {
+                        return Err(err);
+                    }
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                }
+            }
+        }
+    }
+
+    /// Perform a single download attempt without any retry logic.
+    async fn try_download(&self, url: &str, dest: &Path) -> Result<PathBuf, FetchError> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+
+        let part = part_path(dest);
+        let mut file = tokio::fs::File::create(&part).await?;
+
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        drop(file);
+
+        tokio::fs::rename(&part, dest).await?;
+        Ok(dest.to_path_buf())
+    }
+}
+
+/// The temporary download path for `dest`: the same path with a `.part` suffix.
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Return a pseudo-random jitter between zero and half of `delay`.
+///
+/// The crate pulls in no random number generator, so the sub-second nanoseconds of the wall clock are
+/// used as a cheap entropy source — it only needs to desynchronise concurrent retries, not to be
+/// cryptographically sound.
+fn jitter(delay: Duration) -> Duration {
+    let span = delay.as_millis() as u64 / 2 + 1;
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+
+    Duration::from_millis(seed % span)
+}
+
+/// Builder for [`Subspedia`], used to customise the underlying client before it is created.
+pub struct SubspediaBuilder {
+    timeout: Duration,
+    max_attempts: u32,
+    base_delay: Duration,
+    cache: Option<Arc<dyn Cache>>,
+    cache_ttl: Duration,
+}
+
+impl SubspediaBuilder {
+    /// Create a builder with the default configuration.
+    pub fn new() -> SubspediaBuilder {
+        SubspediaBuilder {
+            timeout: DEFAULT_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            cache: None,
+            cache_ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Set the timeout applied to every request made by the client.
+    pub fn timeout(mut self, timeout: Duration) -> SubspediaBuilder {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the maximum number of attempts (the first try plus retries) for a transient failure.
+    pub fn max_attempts(mut self, max_attempts: u32) -> SubspediaBuilder {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set the base delay used for the exponential backoff between retries.
+    pub fn base_delay(mut self, base_delay: Duration) -> SubspediaBuilder {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the cache backend the search helpers go through.
+    pub fn cache<C: Cache + 'static>(mut self, cache: C) -> SubspediaBuilder {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Set how long a cached catalog entry stays valid before it is refetched.
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> SubspediaBuilder {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Build the configured [`Subspedia`] client.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the underlying client (including its TLS backend) fails to initialize.
+    pub fn build(self) -> Result<Subspedia, FetchError> {
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()?;
+
+        Ok(Subspedia {
+            client,
+            max_attempts: self.max_attempts,
+            base_delay: self.base_delay,
+            cache: self.cache,
+            cache_ttl: self.cache_ttl,
+        })
+    }
+}
+
+impl Default for SubspediaBuilder {
+    fn default() -> SubspediaBuilder {
+        SubspediaBuilder::new()
+    }
+}
+
 ///Makes a request based on given type
 ///
+/// This is a blocking wrapper around [`Subspedia::get`] kept for back-compat; it builds a
+/// throwaway client and drives the async call to completion.
+///
 /// # Errors
 ///
 /// Returns error if something gone wrong during http requests and parsing json.
@@ -157,27 +522,13 @@ impl Request for ReqSottotitoliSerie {
 ///use subspedia::ReqSerieTraduzione;
 ///
 ///fn main() {
-///    println!("{:#?}", subspedia::get(ReqSerieTraduzione).unwrap());
+///    println!("{:#?}", subspedia::get(&ReqSerieTraduzione).unwrap());
 ///}
 /// ```
-pub fn get<R: 'static + Request>(req: &R) -> Result<Vec<R::Response>, FetchError>
-{
-    let url = req.url().parse().unwrap();
-    let result = Arc::new(Mutex::new(Vec::new()));
-
-    let tmp = Arc::clone(&result);
-
-    tokio::run(futures::lazy(move || {
-        fetch_json::<R::Response>(url)
-            // use the parsed vector
-            .map(move |mut serie| {
-                tmp.lock().unwrap().append(&mut serie);
-            })
-            // if there was an error print it
-            .map_err(|e| eprintln!("{}", e))
-    }));
-
-    Ok(Arc::try_unwrap(result).unwrap().into_inner().unwrap())
+pub fn get<R: Request>(req: &R) -> Result<Vec<R::Response>, FetchError> {
+    let subspedia = Subspedia::new()?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(subspedia.get(req))
 }
 
 ///Search serie based on a given name
@@ -248,27 +599,3 @@ pub fn search_by_id(id: usize) -> Result<Serie, FetchError> {
         None => Err(FetchError::NotFound(format!("Series with id {} not found.", id)))
     }
 }
-
-fn fetch_json<T>(url: hyper::Uri) -> impl Future<Item=Vec<T>, Error=FetchError>
-    where T: serde::de::DeserializeOwned + std::fmt::Debug
-{
-    let https = hyper_tls::HttpsConnector::new(4).unwrap();
-    let client = hyper::Client::builder()
-        .build::<_, hyper::Body>(https);
-
-    client
-        // Fetch the url...
-        .get(url)
-        // And then, if we get a response back...
-        .and_then(|res| {
-            // asynchronously concatenate chunks of the body
-            res.into_body().concat2()
-        })
-        .from_err::<FetchError>()
-        // use the body after concatenation
-        .and_then(|body| {
-            // try to parse as json with serde_json
-            let serie = serde_json::from_slice(&body)?;
-            Ok(serie)
-        })
-}
\ No newline at end of file
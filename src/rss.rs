@@ -0,0 +1,123 @@
+//! Generation of an RSS 2.0 feed from the subtitles returned by the api.
+//!
+//! This module is only compiled when the `rss` feature is enabled and turns the result of
+//! [`ReqUltimiSottotitoli`](crate::ReqUltimiSottotitoli) (or
+//! [`ReqSottotitoliSerie`](crate::ReqSottotitoliSerie)) into a document that can be self-hosted as
+//! a subtitle-release feed.
+
+use quick_xml::escape::escape;
+
+use crate::{FetchError, Sottotitolo};
+
+/// Names of the days of the week, Sunday first, as used by RFC 822.
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Names of the months, January first, as used by RFC 822.
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Turn a slice of [`Sottotitolo`] into a valid RSS 2.0 document.
+///
+/// Each subtitle becomes an `<item>`: the series name with season and episode goes in the
+/// `<title>`, the description in `<description>`, the subtitle page in `<link>`, the release date
+/// re-emitted as RFC 822 in `<pubDate>` and the image as an `<enclosure>`.
+///
+/// # Errors
+///
+/// Returns [`FetchError::Rss`] if a release date cannot be parsed into a valid RFC 822 date.
+pub fn to_rss(items: &[Sottotitolo]) -> Result<String, FetchError> {
+    let mut body = String::new();
+
+    for item in items {
+        let title = format!(
+            "{} - {}x{:02}",
+            item.nome_serie, item.num_stagione, item.num_episodio
+        );
+
+        body.push_str("    <item>\n");
+        body.push_str(&format!("      <title>{}</title>\n", escape(&title)));
+        body.push_str(&format!(
+            "      <link>{}</link>\n",
+            escape(&item.link_sottotitoli)
+        ));
+        body.push_str(&format!(
+            "      <description>{}</description>\n",
+            escape(&item.descrizione)
+        ));
+        body.push_str(&format!(
+            "      <pubDate>{}</pubDate>\n",
+            escape(&to_rfc822(&item.data_uscita)?)
+        ));
+        // RSS 2.0 requires url, length and type on an enclosure; the api gives no file size, so a
+        // length of 0 is emitted to keep the element valid.
+        body.push_str(&format!(
+            "      <enclosure url=\"{}\" length=\"0\" type=\"image/jpeg\" />\n",
+            escape(&item.immagine)
+        ));
+        body.push_str("    </item>\n");
+    }
+
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <rss version=\"2.0\">\n\
+         \x20 <channel>\n\
+         \x20   <title>Subspedia - ultimi sottotitoli</title>\n\
+         \x20   <link>https://www.subspedia.tv/</link>\n\
+         \x20   <description>Latest subtitles released on subspedia.tv</description>\n\
+         {}\
+         \x20 </channel>\n\
+         </rss>\n",
+        body
+    ))
+}
+
+/// Parse a `YYYY-MM-DD HH:MM:SS` date as returned by the api and re-emit it as an RFC 822 date.
+fn to_rfc822(raw: &str) -> Result<String, FetchError> {
+    let mut numbers = raw.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty());
+
+    let mut next = |name: &str| -> Result<usize, FetchError> {
+        numbers
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| FetchError::Rss(format!("invalid date {}: missing {}", raw, name)))
+    };
+
+    let year = next("year")?;
+    let month = next("month")?;
+    let day = next("day")?;
+    let hour = next("hour")?;
+    let minute = next("minute")?;
+    let second = next("second")?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(FetchError::Rss(format!("invalid date {}", raw)));
+    }
+
+    Ok(format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000",
+        DAYS[day_of_week(year, month, day)],
+        day,
+        MONTHS[month - 1],
+        year,
+        hour,
+        minute,
+        second
+    ))
+}
+
+/// Day of the week (0 = Sunday) for a Gregorian date, via Zeller's congruence.
+fn day_of_week(year: usize, month: usize, day: usize) -> usize {
+    let (y, m) = if month < 3 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+
+    let k = y % 100;
+    let j = y / 100;
+    let h = (day + 13 * (m + 1) / 5 + k + k / 4 + j / 4 + 5 * j) % 7;
+
+    // Zeller's `h` counts Saturday as 0; shift so Sunday is 0 to match `DAYS`.
+    (h + 6) % 7
+}